@@ -0,0 +1,301 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+#[cfg(not(feature = "sqlite"))]
+use diesel::sql_types::BigInt;
+use serde::{Deserialize, Serialize};
+
+// Not part of the generated `schema.rs` yet (added by the migrations under
+// `migrations/postgres` and `migrations/sqlite`), declared locally until the
+// next `diesel print-schema` pass picks it up.
+//
+// The `job` payload column differs by backend (Postgres has `Jsonb`, Sqlite
+// stores the same JSON serialized as `Text`), so the whole table and the
+// structs built on it are duplicated behind the `sqlite` feature rather than
+// shared, the same split used elsewhere in this module for claim/reap/etc.
+#[cfg(not(feature = "sqlite"))]
+diesel::table! {
+    job_queue (id) {
+        id -> Integer,
+        scan_event_id -> Integer,
+        job -> Jsonb,
+        status -> Text,
+        heartbeat -> Nullable<Timestamp>,
+        available_at -> Timestamp,
+        created_at -> Timestamp,
+    }
+}
+
+#[cfg(feature = "sqlite")]
+diesel::table! {
+    job_queue (id) {
+        id -> Integer,
+        scan_event_id -> Integer,
+        job -> Text,
+        status -> Text,
+        heartbeat -> Nullable<Timestamp>,
+        available_at -> Timestamp,
+        created_at -> Timestamp,
+    }
+}
+
+/// Body of a queued job. Currently only scan-event processing is routed
+/// through the queue, but the `job` column carries the full payload (rather
+/// than just `scan_event_id`) so new job kinds can be added without another
+/// migration.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Job {
+    ProcessScanEvent { scan_event_id: i32 },
+}
+
+#[derive(Queryable, QueryableByName, Selectable, Identifiable, Debug)]
+#[diesel(table_name = job_queue)]
+pub struct QueuedJob {
+    pub id: i32,
+    pub scan_event_id: i32,
+    #[cfg(not(feature = "sqlite"))]
+    job: serde_json::Value,
+    #[cfg(feature = "sqlite")]
+    job: String,
+    pub status: String,
+    pub heartbeat: Option<NaiveDateTime>,
+    pub available_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+impl QueuedJob {
+    pub fn job(&self) -> anyhow::Result<Job> {
+        #[cfg(not(feature = "sqlite"))]
+        {
+            serde_json::from_value(self.job.clone()).map_err(Into::into)
+        }
+
+        #[cfg(feature = "sqlite")]
+        {
+            serde_json::from_str(&self.job).map_err(Into::into)
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = job_queue)]
+struct NewJob {
+    scan_event_id: i32,
+    #[cfg(not(feature = "sqlite"))]
+    job: serde_json::Value,
+    #[cfg(feature = "sqlite")]
+    job: String,
+    available_at: NaiveDateTime,
+}
+
+pub const STATUS_NEW: &str = "new";
+pub const STATUS_RUNNING: &str = "running";
+
+#[cfg(not(feature = "sqlite"))]
+fn serialize_job(job: &Job) -> anyhow::Result<serde_json::Value> {
+    serde_json::to_value(job).map_err(Into::into)
+}
+
+#[cfg(feature = "sqlite")]
+fn serialize_job(job: &Job) -> anyhow::Result<String> {
+    serde_json::to_string(job).map_err(Into::into)
+}
+
+/// Queues processing for a scan event, available as soon as `available_at`.
+/// A `scan_event_id` that's already queued (new or running) is a no-op
+/// rather than a second row, via the table's unique constraint, so the same
+/// event is never queued twice (exclusive claiming is `claim_batch`'s job).
+#[cfg(not(feature = "sqlite"))]
+pub fn enqueue_scan_event(
+    conn: &mut PgConnection,
+    scan_event_id: i32,
+    available_at: NaiveDateTime,
+) -> anyhow::Result<()> {
+    diesel::insert_into(job_queue::table)
+        .values(NewJob {
+            scan_event_id,
+            job: serialize_job(&Job::ProcessScanEvent { scan_event_id })?,
+            available_at,
+        })
+        .on_conflict(job_queue::scan_event_id)
+        .do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+pub fn enqueue_scan_event(
+    conn: &mut SqliteConnection,
+    scan_event_id: i32,
+    available_at: NaiveDateTime,
+) -> anyhow::Result<()> {
+    diesel::insert_into(job_queue::table)
+        .values(NewJob {
+            scan_event_id,
+            job: serialize_job(&Job::ProcessScanEvent { scan_event_id })?,
+            available_at,
+        })
+        .on_conflict(job_queue::scan_event_id)
+        .do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Atomically claims up to `limit` queued jobs that are due (`available_at`
+/// has passed) for this worker.
+///
+/// On Postgres this uses `FOR UPDATE SKIP LOCKED` so multiple autopulse
+/// instances pointed at the same database can drain the queue without
+/// double-processing a job. Sqlite has no `SKIP LOCKED`, so it falls back to
+/// a plain transactional claim, which is safe for a single writer.
+#[cfg(not(feature = "sqlite"))]
+pub fn claim_batch(conn: &mut PgConnection, limit: i64) -> anyhow::Result<Vec<QueuedJob>> {
+    diesel::sql_query(
+        "UPDATE job_queue SET status = 'running', heartbeat = now() \
+         WHERE id IN ( \
+             SELECT id FROM job_queue \
+             WHERE status = 'new' AND available_at <= now() \
+             ORDER BY available_at LIMIT $1 FOR UPDATE SKIP LOCKED \
+         ) RETURNING id, scan_event_id, job, status, heartbeat, available_at, created_at",
+    )
+    .bind::<BigInt, _>(limit)
+    .load(conn)
+    .map_err(Into::into)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn claim_batch(conn: &mut SqliteConnection, limit: i64) -> anyhow::Result<Vec<QueuedJob>> {
+    conn.transaction(|conn| {
+        let ids: Vec<i32> = job_queue::table
+            .filter(job_queue::status.eq(STATUS_NEW))
+            .filter(job_queue::available_at.le(diesel::dsl::now))
+            .order(job_queue::available_at.asc())
+            .limit(limit)
+            .select(job_queue::id)
+            .load(conn)?;
+
+        diesel::update(job_queue::table.filter(job_queue::id.eq_any(&ids)))
+            .set((
+                job_queue::status.eq(STATUS_RUNNING),
+                job_queue::heartbeat.eq(diesel::dsl::now),
+            ))
+            .execute(conn)?;
+
+        job_queue::table
+            .filter(job_queue::id.eq_any(&ids))
+            .load(conn)
+    })
+    .map_err(Into::into)
+}
+
+/// Bumps the heartbeat on a claimed job so the reaper knows this worker is
+/// still alive and processing it. Callers processing a job should call this
+/// on an interval shorter than the reap timeout for the whole duration of
+/// the work, not just once at claim time.
+#[cfg(not(feature = "sqlite"))]
+pub fn heartbeat(conn: &mut PgConnection, job_id: i32) -> anyhow::Result<()> {
+    diesel::update(job_queue::table.filter(job_queue::id.eq(job_id)))
+        .set(job_queue::heartbeat.eq(diesel::dsl::now))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+pub fn heartbeat(conn: &mut SqliteConnection, job_id: i32) -> anyhow::Result<()> {
+    diesel::update(job_queue::table.filter(job_queue::id.eq(job_id)))
+        .set(job_queue::heartbeat.eq(diesel::dsl::now))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Requeues any `running` job whose heartbeat is older than `timeout`,
+/// recovering work left behind by a worker that crashed mid-process.
+#[cfg(not(feature = "sqlite"))]
+pub fn reap_stale(conn: &mut PgConnection, timeout: chrono::Duration) -> anyhow::Result<usize> {
+    let cutoff = chrono::Utc::now().naive_utc() - timeout;
+
+    diesel::update(
+        job_queue::table
+            .filter(job_queue::status.eq(STATUS_RUNNING))
+            .filter(job_queue::heartbeat.lt(cutoff)),
+    )
+    .set((
+        job_queue::status.eq(STATUS_NEW),
+        job_queue::heartbeat.eq(None::<NaiveDateTime>),
+    ))
+    .execute(conn)
+    .map_err(Into::into)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn reap_stale(conn: &mut SqliteConnection, timeout: chrono::Duration) -> anyhow::Result<usize> {
+    let cutoff = chrono::Utc::now().naive_utc() - timeout;
+
+    diesel::update(
+        job_queue::table
+            .filter(job_queue::status.eq(STATUS_RUNNING))
+            .filter(job_queue::heartbeat.lt(cutoff)),
+    )
+    .set((
+        job_queue::status.eq(STATUS_NEW),
+        job_queue::heartbeat.eq(None::<NaiveDateTime>),
+    ))
+    .execute(conn)
+    .map_err(Into::into)
+}
+
+/// Deletes a claim once its event reaches a terminal state (complete/failed).
+#[cfg(not(feature = "sqlite"))]
+pub fn complete(conn: &mut PgConnection, job_id: i32) -> anyhow::Result<()> {
+    diesel::delete(job_queue::table.filter(job_queue::id.eq(job_id))).execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+pub fn complete(conn: &mut SqliteConnection, job_id: i32) -> anyhow::Result<()> {
+    diesel::delete(job_queue::table.filter(job_queue::id.eq(job_id))).execute(conn)?;
+
+    Ok(())
+}
+
+/// Releases a claim back to `new` for a scan event that still needs another
+/// pass (retry backoff), due again at `available_at`, instead of leaving it
+/// `running` until the reaper times it out.
+#[cfg(not(feature = "sqlite"))]
+pub fn reschedule(
+    conn: &mut PgConnection,
+    job_id: i32,
+    available_at: NaiveDateTime,
+) -> anyhow::Result<()> {
+    diesel::update(job_queue::table.filter(job_queue::id.eq(job_id)))
+        .set((
+            job_queue::status.eq(STATUS_NEW),
+            job_queue::heartbeat.eq(None::<NaiveDateTime>),
+            job_queue::available_at.eq(available_at),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+pub fn reschedule(
+    conn: &mut SqliteConnection,
+    job_id: i32,
+    available_at: NaiveDateTime,
+) -> anyhow::Result<()> {
+    diesel::update(job_queue::table.filter(job_queue::id.eq(job_id)))
+        .set((
+            job_queue::status.eq(STATUS_NEW),
+            job_queue::heartbeat.eq(None::<NaiveDateTime>),
+            job_queue::available_at.eq(available_at),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
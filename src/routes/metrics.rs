@@ -0,0 +1,16 @@
+use actix_web::{get, HttpResponse, Responder, Result};
+use tracing::error;
+
+use crate::utils::metrics::handle;
+
+#[get("/metrics")]
+pub async fn metrics() -> Result<impl Responder> {
+    let Some(handle) = handle() else {
+        error!("metrics recorder not installed");
+        return Ok(HttpResponse::InternalServerError().finish());
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(handle.render()))
+}
@@ -0,0 +1,11 @@
+use actix_web::web;
+
+mod metrics;
+mod stats;
+
+pub use metrics::metrics;
+pub use stats::stats;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(stats).service(metrics);
+}
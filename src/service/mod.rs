@@ -1,17 +1,25 @@
-use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use notify::RecommendedWatcher;
 
 use crate::{
     db::{
+        job_queue,
         models::{FoundStatus, NewScanEvent, ScanEvent},
         schema::{
             self,
-            scan_events::{
-                dsl::scan_events, found_at, found_status, id, next_retry_at, process_status,
-            },
+            scan_events::{dsl::scan_events, found_at, found_status, id, process_status},
         },
     },
     service::webhooks::WebhookManager,
-    utils::{conn::get_conn, settings::Settings},
+    utils::{
+        conn::get_conn,
+        settings::{Settings, Target},
+    },
     DbPool,
 };
 use diesel::{
@@ -19,6 +27,7 @@ use diesel::{
     SelectableHelper,
 };
 use serde::Serialize;
+use tokio::sync::{mpsc, Semaphore};
 use tracing::{error, info};
 use webhooks::EventType;
 
@@ -40,6 +49,9 @@ pub struct PulseService {
     pub settings: Settings,
     pub pool: DbPool,
     pub webhooks: WebhookManager,
+    // a `notify::Watcher` stops watching once dropped, so the handles spawned
+    // by `start()` are held here for the life of the service
+    watchers: Arc<Mutex<Vec<RecommendedWatcher>>>,
 }
 
 struct PulseRunner {
@@ -99,6 +111,8 @@ impl PulseRunner {
                 if count.len() > 1 { "s" } else { "" }
             );
 
+            metrics::counter!("autopulse_events_found_total").increment(count.len() as u64);
+
             self.webhooks.send(EventType::Found, None, &count).await;
         }
 
@@ -110,28 +124,108 @@ impl PulseRunner {
         let mut failed = vec![];
 
         let mut conn = get_conn(&self.pool);
-        let mut evs = {
-            let base_query = scan_events
-                .filter(process_status.ne(crate::db::models::ProcessStatus::Complete))
-                .filter(process_status.ne(crate::db::models::ProcessStatus::Failed))
-                .filter(
-                    next_retry_at
-                        .is_null()
-                        .or(next_retry_at.lt(chrono::Utc::now().naive_utc())),
-                );
 
+        // requeue claims left behind by a worker that crashed mid-process
+        job_queue::reap_stale(
+            &mut conn,
+            chrono::Duration::seconds(self.settings.opts.job_heartbeat_timeout),
+        )?;
+
+        // claiming is driven entirely by the queue now: `add_event` enqueues
+        // a row up front, and a `Retry` reschedules its own claim below, so
+        // there's no full `scan_events` scan left on this path
+        let claimed = job_queue::claim_batch(&mut conn, self.settings.opts.max_concurrent as i64)?;
+
+        if claimed.is_empty() {
+            return Ok(());
+        }
+
+        let mut job_id_by_scan_event = HashMap::with_capacity(claimed.len());
+
+        for claim in &claimed {
+            job_id_by_scan_event.insert(claim.scan_event_id, claim.id);
+        }
+
+        let evs = scan_events
+            .filter(id.eq_any(job_id_by_scan_event.keys().copied().collect::<Vec<_>>()))
+            .load::<ScanEvent>(&mut conn)?;
+
+        // `check_path` exists to withhold processing until the file is
+        // confirmed on disk, but the queue claims a job as soon as it's
+        // enqueued; release the claim on anything not yet `Found` instead of
+        // processing it so `update_found_status` gets another look at it
+        let (evs, not_found): (Vec<ScanEvent>, Vec<ScanEvent>) =
             if self.settings.opts.check_path {
-                base_query
-                    .filter(found_status.eq(FoundStatus::Found))
-                    .load::<ScanEvent>(&mut conn)?
+                evs.into_iter()
+                    .partition(|ev| matches!(ev.found_status, FoundStatus::Found))
             } else {
-                base_query.load::<ScanEvent>(&mut conn)?
+                (evs, vec![])
+            };
+
+        for ev in &not_found {
+            if let Some(job_id) = job_id_by_scan_event.get(&ev.id) {
+                let available_at = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(5);
+                job_queue::reschedule(&mut conn, *job_id, available_at)?;
             }
-        };
+        }
 
-        for ev in &mut evs {
-            let res = self.process_event(ev).await;
+        if evs.is_empty() {
+            return Ok(());
+        }
+
+        let semaphore = Arc::new(Semaphore::new(self.settings.opts.max_concurrent.max(1)));
+        let (tx, mut rx) = mpsc::channel(evs.len().max(1));
+
+        // bump the heartbeat well inside the reap window, or a job can get
+        // reaped (and re-claimed elsewhere) before its first bump lands
+        let heartbeat_interval = std::time::Duration::from_secs(
+            (self.settings.opts.job_heartbeat_timeout / 3).max(1) as u64,
+        );
+
+        for ev in evs {
+            let semaphore = semaphore.clone();
+            let targets = self.settings.targets.clone();
+            let tx = tx.clone();
+            let pool = self.pool.clone();
+            let job_id = job_id_by_scan_event.get(&ev.id).copied();
+
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+
+                // keep the claim alive in the DB for as long as this event
+                // is actually being worked, so a slow pass doesn't get
+                // reaped and re-claimed by another worker mid-flight
+                let heartbeat_task = job_id.map(|job_id| {
+                    let pool = pool.clone();
+
+                    tokio::spawn(async move {
+                        let mut ticker = tokio::time::interval(heartbeat_interval);
+                        ticker.tick().await;
+
+                        loop {
+                            ticker.tick().await;
+
+                            let mut conn = get_conn(&pool);
+                            if let Err(e) = job_queue::heartbeat(&mut conn, job_id) {
+                                error!("failed to bump heartbeat for job {}: {:?}", job_id, e);
+                            }
+                        }
+                    })
+                });
+
+                let res = Self::process_targets(targets, &ev).await;
+
+                if let Some(task) = heartbeat_task {
+                    task.abort();
+                }
+
+                let _ = tx.send((ev, res)).await;
+            });
+        }
 
+        drop(tx);
+
+        while let Some((mut ev, res)) = rx.recv().await {
             if let Ok((succeeded, _)) = &res {
                 ev.targets_hit.append(&mut succeeded.clone());
             }
@@ -158,6 +252,26 @@ impl PulseRunner {
 
             ev.updated_at = chrono::Utc::now().naive_utc();
             ev.save_changes::<ScanEvent>(&mut conn)?;
+
+            if let Some(job_id) = job_id_by_scan_event.get(&ev.id) {
+                match ev.process_status {
+                    crate::db::models::ProcessStatus::Complete
+                    | crate::db::models::ProcessStatus::Failed => {
+                        job_queue::complete(&mut conn, *job_id)?;
+                    }
+                    _ => {
+                        // still needs another pass: release the claim and
+                        // make it due again at the retry backoff instead of
+                        // re-enqueueing (and instead of leaving it `running`
+                        // for the reaper to eventually time out)
+                        let available_at = ev
+                            .next_retry_at
+                            .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+
+                        job_queue::reschedule(&mut conn, *job_id, available_at)?;
+                    }
+                }
+            }
         }
 
         if !processed.is_empty() {
@@ -167,6 +281,9 @@ impl PulseRunner {
                 if processed.len() > 1 { "s" } else { "" }
             );
 
+            metrics::counter!("autopulse_events_processed_total")
+                .increment(processed.len() as u64);
+
             self.webhooks
                 .send(EventType::Processed, None, &processed)
                 .await;
@@ -179,30 +296,41 @@ impl PulseRunner {
                 if failed.len() > 1 { "s" } else { "" }
             );
 
+            metrics::counter!("autopulse_events_failed_total").increment(failed.len() as u64);
+
             self.webhooks.send(EventType::Error, None, &failed).await;
         }
 
         Ok(())
     }
 
-    async fn process_event(
-        &mut self,
+    async fn process_targets(
+        mut targets: HashMap<String, Target>,
         ev: &ScanEvent,
     ) -> anyhow::Result<(Vec<String>, Vec<String>)> {
         let mut succeeded = vec![];
         let mut failed = vec![];
 
-        for (name, target) in &mut self.settings.targets {
+        for (name, target) in &mut targets {
             if !ev.targets_hit.is_empty() && ev.targets_hit.contains(name) {
                 continue;
             }
 
+            let start = std::time::Instant::now();
             let res = target.process(ev).await;
 
+            metrics::histogram!("autopulse_target_process_seconds", "target" => name.clone())
+                .record(start.elapsed().as_secs_f64());
+
             match res {
                 Ok(()) => succeeded.push(name.clone()),
                 Err(e) => {
                     failed.push(name.clone());
+                    // actual retries happen invisibly inside the
+                    // reqwest-middleware retry stack (utils::http); this
+                    // counts final failures per target, not individual retries
+                    metrics::counter!("autopulse_target_failures_total", "target" => name.clone())
+                        .increment(1);
                     error!("failed to process target '{}': {:?}", name, e);
                 }
             }
@@ -244,10 +372,17 @@ impl PulseRunner {
 
 impl PulseService {
     pub fn new(settings: Settings, pool: DbPool) -> Self {
+        // installs the global Prometheus recorder so the `metrics::counter!`/
+        // `histogram!` calls in `PulseRunner` aren't no-ops, and the
+        // `/metrics` route (registered in `routes::configure`) has something
+        // to render
+        crate::utils::metrics::install_recorder();
+
         Self {
             settings: settings.clone(),
             pool,
             webhooks: WebhookManager::new(settings),
+            watchers: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -286,11 +421,23 @@ impl PulseService {
     pub fn add_event(&self, ev: &NewScanEvent) -> anyhow::Result<ScanEvent> {
         let mut conn = get_conn(&self.pool);
 
-        diesel::insert_into(schema::scan_events::table)
+        let scan_event = diesel::insert_into(schema::scan_events::table)
             .values(ev)
             .returning(ScanEvent::as_returning())
-            .get_result::<ScanEvent>(&mut conn)
-            .map_err(Into::into)
+            .get_result::<ScanEvent>(&mut conn)?;
+
+        if let Err(e) = job_queue::enqueue_scan_event(
+            &mut conn,
+            scan_event.id,
+            chrono::Utc::now().naive_utc(),
+        ) {
+            error!(
+                "failed to enqueue job for scan event {}: {:?}",
+                scan_event.id, e
+            );
+        }
+
+        Ok(scan_event)
     }
 
     pub fn get_event(&self, scan_id: &i32) -> Option<ScanEvent> {
@@ -316,5 +463,16 @@ impl PulseService {
                 timer.tick().await;
             }
         });
+
+        for trigger in self.settings.triggers.values() {
+            let Some(notify) = trigger.as_notify() else {
+                continue;
+            };
+
+            match notify.watch(self.clone()) {
+                Ok(watcher) => self.watchers.lock().unwrap().push(watcher),
+                Err(e) => error!("failed to start notify watcher: {:?}", e),
+            }
+        }
     }
 }
@@ -0,0 +1,3 @@
+pub mod notify;
+pub mod radarr;
+pub mod sonarr;
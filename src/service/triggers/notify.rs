@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use notify::{recommended_watcher, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::{debug, error, warn};
+
+use crate::{db::models::NewScanEvent, service::PulseService, utils::settings::Rewrite};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Notify {
+    pub paths: Vec<String>,
+    pub recursive: Option<bool>,
+    pub excludes: Vec<String>,
+    pub rewrite: Option<Rewrite>,
+    pub timer: Option<u64>,
+}
+
+impl Notify {
+    pub fn async_watcher(
+        &self,
+    ) -> anyhow::Result<(RecommendedWatcher, mpsc::Receiver<notify::Result<Event>>)> {
+        let (tx, rx) = mpsc::channel(100);
+
+        let mut watcher = recommended_watcher(move |res| {
+            if let Err(e) = tx.blocking_send(res) {
+                warn!("failed to forward notify event: {:?}", e);
+            }
+        })?;
+
+        let mode = if self.recursive.unwrap_or(true) {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+
+        for path in &self.paths {
+            watcher.watch(Path::new(path), mode)?;
+        }
+
+        Ok((watcher, rx))
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.excludes.iter().any(|pattern| {
+            glob::Pattern::new(pattern)
+                .map(|pattern| pattern.matches_path(path))
+                .unwrap_or(false)
+        })
+    }
+
+    fn rewrite_path(&self, path: &Path) -> String {
+        let path = path.to_string_lossy().to_string();
+
+        match &self.rewrite {
+            Some(rewrite) => path.replacen(&rewrite.from, &rewrite.to, 1),
+            None => path,
+        }
+    }
+
+    /// Buffers raw watcher events per path and only yields a path once it's
+    /// been quiet for `timer` milliseconds.
+    ///
+    /// A file being written produces many `Modify` events for the same path,
+    /// so without this a large write would turn into hundreds of downstream
+    /// target refreshes instead of exactly one.
+    pub(crate) fn debounce(
+        &self,
+        mut rx: mpsc::Receiver<notify::Result<Event>>,
+    ) -> mpsc::Receiver<String> {
+        let (tx, out_rx) = mpsc::channel(100);
+
+        let this = self.clone();
+        let debounce = Duration::from_millis(this.timer.unwrap_or(600));
+
+        tokio::spawn(async move {
+            let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+            let mut ticker = tokio::time::interval(Duration::from_millis(100));
+
+            loop {
+                tokio::select! {
+                    event = rx.recv() => {
+                        let Some(event) = event else { break };
+
+                        let event = match event {
+                            Ok(event) => event,
+                            Err(e) => {
+                                warn!("notify watcher error: {:?}", e);
+                                continue;
+                            }
+                        };
+
+                        for path in event.paths {
+                            if this.is_excluded(&path) {
+                                continue;
+                            }
+
+                            pending.insert(path, Instant::now());
+                        }
+                    }
+                    _ = ticker.tick() => {
+                        let now = Instant::now();
+                        let ready: Vec<PathBuf> = pending
+                            .iter()
+                            .filter(|(_, seen)| now.duration_since(**seen) >= debounce)
+                            .map(|(path, _)| path.clone())
+                            .collect();
+
+                        for path in ready {
+                            pending.remove(&path);
+
+                            let file_path = this.rewrite_path(&path);
+                            debug!("notify: emitting scan event for {}", file_path);
+
+                            if tx.send(file_path).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        out_rx
+    }
+
+    /// Spawns the watcher and forwards its debounced events to `service` as
+    /// `NewScanEvent`s.
+    pub fn watch(&self, service: PulseService) -> anyhow::Result<RecommendedWatcher> {
+        let (watcher, rx) = self.async_watcher()?;
+        let mut debounced = self.debounce(rx);
+
+        tokio::spawn(async move {
+            while let Some(file_path) = debounced.recv().await {
+                if let Err(e) = service.add_event(&NewScanEvent { file_path }) {
+                    error!("failed to add scan event from notify: {:?}", e);
+                }
+            }
+        });
+
+        Ok(watcher)
+    }
+}
@@ -0,0 +1,44 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::utils::http::build_client;
+
+use super::EventType;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiscordWebhook {
+    pub url: String,
+}
+
+impl DiscordWebhook {
+    pub async fn send(
+        &self,
+        event_type: EventType,
+        message: Option<&str>,
+        files: &[String],
+    ) -> anyhow::Result<()> {
+        let client = build_client(reqwest::header::HeaderMap::new())?;
+
+        let content = message.map(str::to_string).unwrap_or_else(|| {
+            format!(
+                "**{}**: {} file{}\n{}",
+                event_type.as_str(),
+                files.len(),
+                if files.len() == 1 { "" } else { "s" },
+                files.join("\n")
+            )
+        });
+
+        let res = client
+            .post(&self.url)
+            .json(&json!({ "content": content }))
+            .send()
+            .await?;
+
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            anyhow::bail!("discord webhook returned {}", res.status())
+        }
+    }
+}
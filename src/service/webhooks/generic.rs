@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use reqwest::Method;
+use serde::Deserialize;
+
+use crate::utils::http::build_client;
+
+use super::EventType;
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+/// A generic HTTP webhook for endpoints that aren't Discord (Slack, Gotify, a
+/// custom receiver, ...). `body` is a template rendered with `{event_type}`,
+/// `{count}` and `{files}` before being POSTed (or sent with `method`).
+#[derive(Deserialize, Clone, Debug)]
+pub struct GenericWebhook {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl GenericWebhook {
+    fn render(&self, event_type: EventType, files: &[String]) -> String {
+        self.body
+            .replace("{event_type}", event_type.as_str())
+            .replace("{count}", &files.len().to_string())
+            .replace("{files}", &files.join(", "))
+    }
+
+    pub async fn send(
+        &self,
+        event_type: EventType,
+        _message: Option<&str>,
+        files: &[String],
+    ) -> anyhow::Result<()> {
+        let mut header_map = reqwest::header::HeaderMap::new();
+
+        for (key, value) in &self.headers {
+            header_map.insert(
+                reqwest::header::HeaderName::from_bytes(key.as_bytes())?,
+                value.parse()?,
+            );
+        }
+
+        let client = build_client(header_map)?;
+        let method = Method::from_bytes(self.method.as_bytes())?;
+        let body = self.render(event_type, files);
+
+        let res = client.request(method, &self.url).body(body).send().await?;
+
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            anyhow::bail!("generic webhook returned {}", res.status())
+        }
+    }
+}
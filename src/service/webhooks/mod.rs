@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use tracing::error;
+
+use crate::utils::settings::{Settings, Webhook};
+
+pub mod discord;
+pub mod generic;
+
+#[derive(Clone, Copy, Debug)]
+pub enum EventType {
+    Found,
+    Processed,
+    Error,
+}
+
+impl EventType {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Found => "found",
+            Self::Processed => "processed",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WebhookManager {
+    webhooks: HashMap<String, Webhook>,
+}
+
+impl WebhookManager {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            webhooks: settings.webhooks,
+        }
+    }
+
+    pub async fn send(&self, event_type: EventType, message: Option<String>, files: &[String]) {
+        if files.is_empty() {
+            return;
+        }
+
+        for (name, webhook) in &self.webhooks {
+            let res = match webhook {
+                Webhook::Discord(discord) => discord.send(event_type, message.as_deref(), files).await,
+                Webhook::Generic(generic) => generic.send(event_type, message.as_deref(), files).await,
+            };
+
+            if let Err(e) = res {
+                error!("failed to send webhook '{}': {:?}", name, e);
+            }
+        }
+    }
+}
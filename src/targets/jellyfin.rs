@@ -1,5 +1,9 @@
-use crate::{db::models::ScanEvent, utils::settings::TargetProcess};
+use crate::{
+    db::models::ScanEvent,
+    utils::{http::build_client, settings::TargetProcess},
+};
 use reqwest::header;
+use reqwest_middleware::ClientWithMiddleware;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
@@ -7,6 +11,26 @@ use tracing::debug;
 pub struct Jellyfin {
     pub url: String,
     pub token: String,
+    pub page_size: Option<usize>,
+    pub metadata_refresh_mode: Option<MetadataRefreshMode>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+pub enum MetadataRefreshMode {
+    Default,
+    ValidationOnly,
+    #[default]
+    FullRefresh,
+}
+
+impl MetadataRefreshMode {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "Default",
+            Self::ValidationOnly => "ValidationOnly",
+            Self::FullRefresh => "FullRefresh",
+        }
+    }
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -41,19 +65,21 @@ struct Item {
 #[serde(rename_all = "PascalCase")]
 struct ItemsResponse {
     items: Vec<Item>,
+    total_record_count: usize,
 }
 
+/// Default page size for `find_item`'s paged `/Items` scan, overridable via
+/// `Jellyfin::page_size`.
+const DEFAULT_PAGE_SIZE: usize = 500;
+
 impl Jellyfin {
-    fn get_client(&self) -> anyhow::Result<reqwest::Client> {
+    fn get_client(&self) -> anyhow::Result<ClientWithMiddleware> {
         let mut headers = header::HeaderMap::new();
 
         headers.insert("X-Emby-Token", self.token.parse().unwrap());
         headers.insert("Accept", "application/json".parse().unwrap());
 
-        reqwest::Client::builder()
-            .default_headers(headers)
-            .build()
-            .map_err(Into::into)
+        build_client(headers)
     }
 
     async fn libraries(&self) -> anyhow::Result<Vec<Library>> {
@@ -68,25 +94,35 @@ impl Jellyfin {
         Ok(libraries)
     }
 
-    // sadly this is quite memory intensive, maybe a stream option is possible
+    // paged so peak memory is one page of the library rather than all of it
     async fn find_item(&self, path: &str) -> anyhow::Result<Option<Item>> {
         let client = self.get_client()?;
-        let mut url = url::Url::parse(&self.url)?.join("/Items")?;
+        let limit = self.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        let mut start_index = 0;
 
-        url.query_pairs_mut().append_pair("Recursive", "true");
-        url.query_pairs_mut().append_pair("Fields", "Path");
-        url.query_pairs_mut().append_pair("EnableImages", "false");
+        loop {
+            let mut url = url::Url::parse(&self.url)?.join("/Items")?;
 
-        let res = client.get(url.to_string()).send().await?;
+            url.query_pairs_mut()
+                .append_pair("Recursive", "true")
+                .append_pair("Fields", "Path")
+                .append_pair("EnableImages", "false")
+                .append_pair("StartIndex", &start_index.to_string())
+                .append_pair("Limit", &limit.to_string());
 
-        let res = res.json::<ItemsResponse>().await?;
+            let res = client.get(url.to_string()).send().await?;
+            let res = res.json::<ItemsResponse>().await?;
 
-        let item = res
-            .items
-            .iter()
-            .find(|item| item.path == Some(path.to_string()));
+            if let Some(item) = res.items.iter().find(|item| item.path.as_deref() == Some(path)) {
+                return Ok(Some(item.clone()));
+            }
 
-        Ok(item.cloned())
+            start_index += res.items.len();
+
+            if res.items.is_empty() || start_index >= res.total_record_count {
+                return Ok(None);
+            }
+        }
     }
 
     // not as effective as refreshing the item, but good enough
@@ -122,9 +158,10 @@ impl Jellyfin {
         let client = self.get_client()?;
         let mut url = url::Url::parse(&self.url)?.join(&format!("/Items/{}/Refresh", item.id))?;
 
-        // TODO: make this a setting the user can choose, along with the other options
-        url.query_pairs_mut()
-            .append_pair("metadataRefreshMode", "FullRefresh");
+        url.query_pairs_mut().append_pair(
+            "metadataRefreshMode",
+            self.metadata_refresh_mode.unwrap_or_default().as_str(),
+        );
 
         let res = client.post(url.to_string()).send().await?;
 
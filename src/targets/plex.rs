@@ -0,0 +1,101 @@
+use crate::{
+    db::models::ScanEvent,
+    utils::{http::build_client, settings::TargetProcess},
+};
+use reqwest::header;
+use reqwest_middleware::ClientWithMiddleware;
+use serde::Deserialize;
+use tracing::debug;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Plex {
+    pub url: String,
+    pub token: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Directory {
+    #[allow(dead_code)]
+    key: String,
+    location: Vec<Location>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Location {
+    path: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct MediaContainer<T> {
+    #[serde(rename = "MediaContainer")]
+    media_container: T,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Directories {
+    directory: Vec<Directory>,
+}
+
+impl Plex {
+    fn get_client(&self) -> anyhow::Result<ClientWithMiddleware> {
+        let mut headers = header::HeaderMap::new();
+
+        headers.insert("X-Plex-Token", self.token.parse().unwrap());
+        headers.insert("Accept", "application/json".parse().unwrap());
+
+        build_client(headers)
+    }
+
+    async fn sections(&self) -> anyhow::Result<Vec<Directory>> {
+        let client = self.get_client()?;
+        let url = url::Url::parse(&self.url)?
+            .join("/library/sections")?
+            .to_string();
+
+        let res = client.get(&url).send().await?;
+        let res: MediaContainer<Directories> = res.json().await?;
+
+        Ok(res.media_container.directory)
+    }
+
+    async fn refresh(&self, directory: &Directory, ev: &ScanEvent) -> anyhow::Result<()> {
+        let client = self.get_client()?;
+        let mut url = url::Url::parse(&self.url)?
+            .join(&format!("/library/sections/{}/refresh", directory.key))?;
+
+        url.query_pairs_mut().append_pair("path", &ev.file_path);
+
+        let res = client.get(url.to_string()).send().await?;
+
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            let body = res.text().await?;
+            Err(anyhow::anyhow!("Failed to refresh section: {}", body))
+        }
+    }
+}
+
+impl TargetProcess for Plex {
+    async fn process(&self, ev: &ScanEvent) -> anyhow::Result<()> {
+        let sections = self.sections().await?;
+
+        let directory = sections
+            .iter()
+            .find(|directory| {
+                directory
+                    .location
+                    .iter()
+                    .any(|location| ev.file_path.starts_with(&location.path))
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!("File path {} not in any plex library", ev.file_path)
+            })?;
+
+        debug!("Found section: {:?}", directory);
+        self.refresh(directory, ev).await
+    }
+}
@@ -1,9 +1,9 @@
 #![cfg(test)]
 mod tests {
     use crate::service::triggers::notify::Notify;
-    use notify::{event::CreateKind, EventKind};
+    use notify::{event::CreateKind, Event, EventKind};
     use std::{env, fs::create_dir, time::Duration};
-    use tokio::time::timeout;
+    use tokio::{sync::mpsc, time::timeout};
     use uuid::Uuid;
 
     #[tokio::test]
@@ -36,4 +36,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_notify_coalesces_repeated_events() -> anyhow::Result<()> {
+        let notifier = Notify {
+            paths: vec![],
+            rewrite: None,
+            recursive: None,
+            excludes: vec![],
+            timer: Some(200),
+        };
+
+        let (tx, rx) = mpsc::channel(100);
+        let path = env::temp_dir().join(Uuid::new_v4().to_string());
+
+        // a write in progress fires many Modify events for the same path
+        for _ in 0..10 {
+            tx.send(Ok(Event {
+                kind: EventKind::Modify(notify::event::ModifyKind::Data(
+                    notify::event::DataChange::Any,
+                )),
+                paths: vec![path.clone()],
+                attrs: Default::default(),
+            }))
+            .await?;
+        }
+
+        let mut debounced = notifier.debounce(rx);
+
+        // nothing should be emitted before the quiet period elapses
+        let too_soon = timeout(Duration::from_millis(50), debounced.recv()).await;
+        assert!(too_soon.is_err(), "event coalesced before timer elapsed");
+
+        let emitted = timeout(Duration::from_secs(1), debounced.recv())
+            .await?
+            .expect("expected a coalesced event");
+        assert_eq!(emitted, path.to_string_lossy().to_string());
+
+        // the ten events should have collapsed into exactly one emission
+        let second = timeout(Duration::from_millis(300), debounced.recv()).await;
+        assert!(second.is_err(), "expected only one coalesced event");
+
+        Ok(())
+    }
 }
\ No newline at end of file
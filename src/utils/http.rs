@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next, Result};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use tracing::{info_span, Instrument};
+
+/// Wraps every outbound request in its own tracing span, the same way
+/// pict-rs' `TracingMiddleware` does.
+struct TracingMiddleware;
+
+#[async_trait]
+impl Middleware for TracingMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let span = info_span!("http_request", method = %req.method(), url = %req.url());
+
+        next.run(req, extensions).instrument(span).await
+    }
+}
+
+/// Builds the HTTP client shared by target and webhook integrations: a
+/// transient 5xx/connect failure is retried with exponential backoff instead
+/// of failing the target/webhook permanently.
+pub fn build_client(headers: reqwest::header::HeaderMap) -> anyhow::Result<ClientWithMiddleware> {
+    let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+
+    let client = reqwest::Client::builder()
+        .default_headers(headers)
+        .timeout(Duration::from_secs(30))
+        .build()?;
+
+    Ok(ClientBuilder::new(client)
+        .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+        .with(TracingMiddleware)
+        .build())
+}
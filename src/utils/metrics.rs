@@ -0,0 +1,27 @@
+use std::sync::OnceLock;
+
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use tracing::error;
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Installs the global Prometheus recorder. Must be called once at startup,
+/// before any `metrics::counter!`/`metrics::histogram!` calls are made.
+pub fn install_recorder() -> PrometheusHandle {
+    HANDLE
+        .get_or_init(|| match PrometheusBuilder::new().install_recorder() {
+            Ok(handle) => handle,
+            Err(e) => {
+                error!("failed to install prometheus recorder: {:?}", e);
+                PrometheusBuilder::new()
+                    .build_recorder()
+                    .handle()
+            }
+        })
+        .clone()
+}
+
+/// Returns the handle installed by [`install_recorder`], if it has run.
+pub fn handle() -> Option<PrometheusHandle> {
+    HANDLE.get().cloned()
+}
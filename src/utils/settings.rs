@@ -7,8 +7,8 @@ use crate::{
     db::models::ScanEvent,
     service::{
         targets::{command::Command, jellyfin::Jellyfin, plex::Plex},
-        triggers::{radarr::RadarrRequest, sonarr::SonarrRequest},
-        webhooks::discord::DiscordWebhook,
+        triggers::{notify::Notify, radarr::RadarrRequest, sonarr::SonarrRequest},
+        webhooks::{discord::DiscordWebhook, generic::GenericWebhook},
     },
 };
 
@@ -17,6 +17,13 @@ pub struct App {
     pub hostname: String,
     pub port: u16,
     pub database_url: String,
+    pub tls: Option<Tls>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Tls {
+    pub cert: String,
+    pub key: String,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -29,6 +36,20 @@ pub struct Auth {
 pub struct Opts {
     pub check_path: bool,
     pub max_retries: i32,
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+    /// Seconds a claimed job queue row may go without a heartbeat before
+    /// the reaper assumes its worker died and requeues it.
+    #[serde(default = "default_job_heartbeat_timeout")]
+    pub job_heartbeat_timeout: i64,
+}
+
+const fn default_max_concurrent() -> usize {
+    4
+}
+
+const fn default_job_heartbeat_timeout() -> i64 {
+    300
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -70,6 +91,13 @@ pub enum Trigger {
     Manual { rewrite: Option<Rewrite> },
     Radarr { rewrite: Option<Rewrite> },
     Sonarr { rewrite: Option<Rewrite> },
+    Notify {
+        paths: Vec<String>,
+        recursive: Option<bool>,
+        excludes: Vec<String>,
+        rewrite: Option<Rewrite>,
+        timer: Option<u64>,
+    },
 }
 
 impl Trigger {
@@ -80,12 +108,34 @@ impl Trigger {
             _ => todo!(),
         }
     }
+
+    /// Returns the `Notify` watcher config for a `Notify` trigger (started
+    /// with `Notify::watch`), or `None` for any other trigger kind.
+    pub fn as_notify(&self) -> Option<Notify> {
+        match self {
+            Trigger::Notify {
+                paths,
+                recursive,
+                excludes,
+                rewrite,
+                timer,
+            } => Some(Notify {
+                paths: paths.clone(),
+                recursive: *recursive,
+                excludes: excludes.clone(),
+                rewrite: rewrite.clone(),
+                timer: *timer,
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Deserialize, Clone, Debug)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Webhook {
     Discord(DiscordWebhook),
+    Generic(GenericWebhook),
 }
 
 pub trait TargetProcess {
@@ -0,0 +1,64 @@
+use std::{fs::File, io::BufReader, net::ToSocketAddrs};
+
+use actix_web::{
+    body::MessageBody,
+    dev::{IntoServiceFactory, ServiceFactory},
+    Error, HttpResponse, HttpServer,
+};
+use rustls::ServerConfig;
+use rustls_pemfile::{certs, private_key};
+
+use crate::utils::settings::{App, Tls};
+
+/// Loads a PEM cert/key pair into a rustls `ServerConfig` for the actix
+/// `HttpServer` to bind with, so Radarr/Sonarr webhooks can hit autopulse
+/// over HTTPS directly instead of through a reverse proxy.
+pub fn server_config(tls: &Tls) -> anyhow::Result<ServerConfig> {
+    // rustls 0.23 has no default crypto backend; install ring once before the
+    // first `ServerConfig::builder()` call or it panics at runtime. Ignore
+    // the error, it just means another caller already installed one.
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let cert_chain = certs(&mut BufReader::new(File::open(&tls.cert)?))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // `private_key` (unlike `pkcs8_private_keys`) recognizes PKCS#1, PKCS#8,
+    // and SEC1/EC PEM keys, so it doesn't silently reject a non-PKCS#8 key.
+    let key = private_key(&mut BufReader::new(File::open(&tls.key)?))?
+        .ok_or_else(|| anyhow::anyhow!("no private key found in {}", tls.key))?;
+
+    ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(cert_chain, key)
+        .map_err(Into::into)
+}
+
+/// Binds `server` to `app.hostname:app.port`, over TLS when `app.tls` is
+/// configured and plain HTTP otherwise.
+pub fn bind<F, I, S, B>(
+    server: HttpServer<F, I, S, B>,
+    app: &App,
+) -> anyhow::Result<HttpServer<F, I, S, B>>
+where
+    F: Fn() -> I + Send + Clone + 'static,
+    I: IntoServiceFactory<S, actix_web::dev::ServiceRequest>,
+    S: ServiceFactory<actix_web::dev::ServiceRequest, Config = actix_web::dev::AppConfig>
+        + 'static,
+    S::Error: Into<Error> + 'static,
+    S::InitError: std::fmt::Debug,
+    S::Response: Into<HttpResponse<B>> + 'static,
+    <S::Service as actix_web::dev::Service<actix_web::dev::ServiceRequest>>::Future: 'static,
+    B: MessageBody + 'static,
+{
+    let addr = (app.hostname.as_str(), app.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("invalid hostname/port: {}:{}", app.hostname, app.port))?;
+
+    let server = match &app.tls {
+        Some(tls) => server.bind_rustls_0_23(addr, server_config(tls)?)?,
+        None => server.bind(addr)?,
+    };
+
+    Ok(server)
+}